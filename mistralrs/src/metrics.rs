@@ -0,0 +1,115 @@
+//! Streaming, newline-delimited JSON metrics for a runner pipeline.
+//!
+//! When a [`MetricsSink`] is configured on the builder, a file sink receives one
+//! self-describing JSON object per line instead of (or alongside) human-readable log
+//! lines, and a callback sink receives each [`MetricsEvent`] value. Every event
+//! carries a `kind` tag so the stream can be piped directly into dashboards or test
+//! harnesses without scraping formatted text. [`MetricsEvent`] defines the full set
+//! of event kinds. The builder emits only [`MetricsEvent::ModelLoaded`], once a model
+//! finishes loading; the other kinds are not emitted by the runner or either
+//! scheduler path.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+/// A callback invoked with each metrics event, analogous to `SearchCallback`.
+pub type MetricsCallback = dyn Fn(&MetricsEvent) + Send + Sync;
+
+/// Where metrics events are delivered.
+#[derive(Clone)]
+pub enum MetricsSink {
+    /// Append newline-delimited JSON to a file.
+    File(PathBuf),
+    /// Hand each event to a user-provided callback.
+    Callback(Arc<MetricsCallback>),
+}
+
+/// A single metrics event. Serialized with an internally-tagged `kind` field.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MetricsEvent {
+    ModelLoaded {
+        model_id: String,
+        dtype: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        isq: Option<String>,
+    },
+    IsqProgress {
+        completed: usize,
+        total: usize,
+    },
+    Prefill {
+        seq_id: usize,
+        tokens: usize,
+        tokens_per_sec: f32,
+    },
+    DecodeStep {
+        seq_id: usize,
+        tokens: usize,
+        tokens_per_sec: f32,
+    },
+    PrefixCacheHit {
+        seq_id: usize,
+        tokens: usize,
+    },
+    PagedBlockAlloc {
+        seq_id: usize,
+        blocks: usize,
+        cache_occupancy: f32,
+    },
+    RequestComplete {
+        seq_id: usize,
+        prompt_tokens: usize,
+        completion_tokens: usize,
+        tokens_per_sec: f32,
+    },
+}
+
+/// Resolved sink used at runtime. Created from a [`MetricsSink`] in `build()`.
+#[derive(Clone)]
+pub struct MetricsEmitter {
+    inner: Arc<Inner>,
+}
+
+enum Inner {
+    Writer(Mutex<BufWriter<File>>),
+    Callback(Arc<MetricsCallback>),
+}
+
+impl MetricsEmitter {
+    /// Resolve a [`MetricsSink`] into an emitter, opening the file if necessary.
+    pub fn new(sink: MetricsSink) -> anyhow::Result<Self> {
+        let inner = match sink {
+            MetricsSink::File(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?;
+                Inner::Writer(Mutex::new(BufWriter::new(file)))
+            }
+            MetricsSink::Callback(cb) => Inner::Callback(cb),
+        };
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Emit a single event, serializing it to one JSON line for file sinks.
+    pub fn emit(&self, event: MetricsEvent) {
+        match &*self.inner {
+            Inner::Writer(writer) => {
+                if let Ok(line) = serde_json::to_string(&event) {
+                    if let Ok(mut w) = writer.lock() {
+                        let _ = writeln!(w, "{line}");
+                        let _ = w.flush();
+                    }
+                }
+            }
+            Inner::Callback(cb) => cb(&event),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Low-overhead, mmap-backed self-profiler.
+//!
+//! The profiler records start/stop pairs for labelled phases without formatting any
+//! strings on the hot path. `TextModelBuilder::build` records `build` with the nested
+//! phases `build_loader`, `load_model_from_hf` (download, weight load, quantization
+//! and cache allocation as one event), `build_scheduler` and `build_runner`; no
+//! events are recorded during generation (prefill or decode). Event labels are interned once into a small integer id;
+//! every subsequent event only writes that id. Each event reserves a fixed-width
+//! slot with a single relaxed `fetch_add` and then writes its bytes directly into
+//! the memory-mapped region — no lock is taken on the emit path.
+//!
+//! Records are little-endian and laid out as:
+//!
+//! ```text
+//! byte  0      : event kind (0 = start, 1 = stop)
+//! bytes 1..5   : interned label id (u32)
+//! bytes 5..9   : thread id (u32)
+//! bytes 9..17  : monotonic timestamp, nanoseconds since profiler start (u64)
+//! bytes 17..24 : reserved
+//! ```
+//!
+//! The file opens with a [`HEADER_SIZE`]-byte header (`magic`, `version`,
+//! `record_size`, wall-clock start) so an offline tool can reconstruct a
+//! flame-style per-label self-time/total-time summary. The interned label table is
+//! written alongside the event file as a `.strings` sidecar on shutdown.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use memmap2::MmapMut;
+
+/// Magic bytes identifying a mistral.rs profiler stream.
+const MAGIC: &[u8; 8] = b"MRSPROF\0";
+/// On-disk format version.
+const VERSION: u32 = 1;
+/// Size of a single event record, in bytes.
+const RECORD_SIZE: usize = 24;
+/// Size of the leading file header, in bytes.
+const HEADER_SIZE: usize = 24;
+/// Capacity of the event region. Records beyond this are dropped rather than grown.
+const DEFAULT_CAPACITY: usize = 64 * 1024 * 1024;
+
+/// Event kind written into byte 0 of each record.
+const KIND_START: u8 = 0;
+const KIND_STOP: u8 = 1;
+
+static NEXT_THREAD_ID: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+    static THREAD_ID: u32 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+    /// Per-thread stack of open start labels. A stop pairs with the most recent
+    /// open start on the calling thread, so nested guards dropped in LIFO order
+    /// close their own starts; a guard dropped out of order closes whichever
+    /// label is on top of the stack. Thread-local rather than a shared map so
+    /// push/pop never contend a lock.
+    static STACK: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+}
+
+fn thread_id() -> u32 {
+    THREAD_ID.with(|id| *id)
+}
+
+/// A handle to the mmap-backed profiler. Cloning is cheap and shares the same
+/// underlying event stream, so the handle can be passed into inference code and
+/// used from multiple threads.
+#[derive(Clone)]
+pub struct Profiler {
+    inner: Arc<ProfilerInner>,
+}
+
+struct ProfilerInner {
+    /// Kept mapped for the profiler's lifetime; flushed and truncated on drop.
+    mmap: MmapMut,
+    /// Cached base pointer into `mmap`. Each emitter writes only the disjoint slot
+    /// it reserved via `head`, so direct writes through this pointer do not alias.
+    base: *mut u8,
+    head: AtomicUsize,
+    capacity: usize,
+    interner: RwLock<Interner>,
+    start: Instant,
+    path: PathBuf,
+}
+
+// Safety: `base` points into `mmap`, which lives as long as the `ProfilerInner`.
+// Writes only ever touch the unique slot reserved by the emitting thread's
+// `fetch_add`, so concurrent emits never write overlapping bytes.
+unsafe impl Send for ProfilerInner {}
+unsafe impl Sync for ProfilerInner {}
+
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<String, u32>,
+    labels: Vec<String>,
+}
+
+impl Profiler {
+    /// Open (creating or truncating) a profiler stream at `path`.
+    pub fn new(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        file.set_len((HEADER_SIZE + DEFAULT_CAPACITY) as u64)?;
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+
+        let wall_start = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        mmap[0..8].copy_from_slice(MAGIC);
+        mmap[8..12].copy_from_slice(&VERSION.to_le_bytes());
+        mmap[12..16].copy_from_slice(&(RECORD_SIZE as u32).to_le_bytes());
+        mmap[16..24].copy_from_slice(&wall_start.to_le_bytes());
+
+        let base = mmap.as_mut_ptr();
+        Ok(Self {
+            inner: Arc::new(ProfilerInner {
+                mmap,
+                base,
+                head: AtomicUsize::new(HEADER_SIZE),
+                capacity: HEADER_SIZE + DEFAULT_CAPACITY,
+                interner: RwLock::new(Interner::default()),
+                start: Instant::now(),
+                path,
+            }),
+        })
+    }
+
+    /// Begin an event with the given label, returning a guard that emits the
+    /// matching stop record when dropped. Events nest: the guard pairs its stop
+    /// with the most recent open start on the calling thread.
+    pub fn event(&self, label: &str) -> EventGuard {
+        let label_id = self.inner.intern(label);
+        STACK.with(|stack| stack.borrow_mut().push(label_id));
+        self.inner.emit(KIND_START, label_id);
+        EventGuard {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl ProfilerInner {
+    fn intern(&self, label: &str) -> u32 {
+        if let Some(id) = self.interner.read().unwrap().ids.get(label) {
+            return *id;
+        }
+        let mut interner = self.interner.write().unwrap();
+        // Re-check: another writer may have interned this label between the read
+        // unlock and the write lock.
+        if let Some(id) = interner.ids.get(label) {
+            return *id;
+        }
+        let id = interner.labels.len() as u32;
+        interner.labels.push(label.to_string());
+        interner.ids.insert(label.to_string(), id);
+        id
+    }
+
+    fn emit(&self, kind: u8, label_id: u32) {
+        // Reserve a disjoint slot with a single atomic bump, then write into it
+        // directly — no lock on the hot path.
+        let off = self.head.fetch_add(RECORD_SIZE, Ordering::Relaxed);
+        if off + RECORD_SIZE > self.capacity {
+            return;
+        }
+        let ts = self.start.elapsed().as_nanos() as u64;
+        let tid = thread_id();
+        unsafe {
+            let rec = self.base.add(off);
+            rec.write(kind);
+            std::ptr::copy_nonoverlapping(label_id.to_le_bytes().as_ptr(), rec.add(1), 4);
+            std::ptr::copy_nonoverlapping(tid.to_le_bytes().as_ptr(), rec.add(5), 4);
+            std::ptr::copy_nonoverlapping(ts.to_le_bytes().as_ptr(), rec.add(9), 8);
+        }
+    }
+}
+
+impl Drop for ProfilerInner {
+    fn drop(&mut self) {
+        // Persist the interned label table next to the event stream so an offline
+        // tool can resolve ids back to names.
+        let strings_path = self.path.with_extension("strings");
+        if let Ok(mut f) = File::create(&strings_path) {
+            if let Ok(interner) = self.interner.read() {
+                for (id, label) in interner.labels.iter().enumerate() {
+                    let _ = writeln!(f, "{id}\t{label}");
+                }
+            }
+            let _ = f.flush();
+        }
+
+        // Flush the written records and truncate the file to the used length.
+        let used = self.head.load(Ordering::Relaxed).min(self.capacity);
+        let _ = self.mmap.flush();
+        if let Ok(file) = OpenOptions::new().write(true).open(&self.path) {
+            let _ = file.set_len(used as u64);
+        }
+    }
+}
+
+/// Guard returned by [`Profiler::event`]. Emits the stop record on drop, paired
+/// with the most recent open start on the dropping thread.
+pub struct EventGuard {
+    inner: Arc<ProfilerInner>,
+}
+
+impl Drop for EventGuard {
+    fn drop(&mut self) {
+        if let Some(label_id) = STACK.with(|stack| stack.borrow_mut().pop()) {
+            self.inner.emit(KIND_STOP, label_id);
+        }
+    }
+}
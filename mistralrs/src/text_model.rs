@@ -1,6 +1,7 @@
 use candle_core::Device;
 use mistralrs_core::*;
 use mistralrs_core::{SearchCallback, Tool, ToolCallback};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::{
     num::NonZeroUsize,
@@ -50,6 +51,8 @@ pub struct TextModelBuilder {
     pub(crate) force_cpu: bool,
     pub(crate) isq: Option<IsqType>,
     pub(crate) throughput_logging: bool,
+    pub(crate) profiler: Option<PathBuf>,
+    pub(crate) json_metrics: Option<crate::metrics::MetricsSink>,
 
     // Other things
     pub(crate) paged_attn_cfg: Option<PagedAttentionConfig>,
@@ -133,6 +136,8 @@ impl TextModelBuilder {
             calibration_file: None,
             jinja_explicit: None,
             throughput_logging: false,
+            profiler: None,
+            json_metrics: None,
             hf_cache_path: None,
             search_bert_model: None,
             search_callback: None,
@@ -192,6 +197,27 @@ impl TextModelBuilder {
         self
     }
 
+    /// Emit newline-delimited JSON metrics instead of scraping formatted log lines.
+    /// The sink is either a file path or a writer callback (analogous to
+    /// `with_search_callback`); each event is a self-describing object tagged with a
+    /// `kind` (see [`crate::metrics::MetricsEvent`]). The builder emits only
+    /// [`crate::metrics::MetricsEvent::ModelLoaded`], once loading completes.
+    pub fn with_json_metrics(mut self, sink: crate::metrics::MetricsSink) -> Self {
+        self.json_metrics = Some(sink);
+        self
+    }
+
+    /// Enable the mmap-backed self-profiler, writing its event stream to `path`.
+    ///
+    /// The profiler records start/stop pairs for the loading phases of `build()` as
+    /// fixed-width records in a memory-mapped append-only stream, with event labels
+    /// interned to small integer ids. Generation (prefill and decode) is not recorded. When left disabled (the default) there is
+    /// zero cost. See [`crate::profiler`] for the on-disk format.
+    pub fn with_profiler(mut self, path: PathBuf) -> Self {
+        self.profiler = Some(path);
+        self
+    }
+
     /// Explicit JINJA chat template file (.jinja) to be used. If specified, this overrides all other chat templates.
     pub fn with_jinja_explicit(mut self, jinja_explicit: String) -> Self {
         self.jinja_explicit = Some(jinja_explicit);
@@ -354,7 +380,105 @@ impl TextModelBuilder {
         self
     }
 
+    /// Serialize the full set of plain-data loading/running parameters to a JSON
+    /// manifest. Callbacks, registered tools, the JSON metrics sink, and the [`Device`]
+    /// handle are skipped, but every other knob round-trips through
+    /// [`Self::from_manifest`]. Useful for reproducible deployments and for checking a
+    /// run's configuration into version control.
+    ///
+    /// Returns an error if any embedded config value fails to serialize rather than
+    /// panicking.
+    pub fn to_manifest(&self) -> anyhow::Result<serde_json::Value> {
+        let manifest = TextModelManifest {
+            model_id: self.model_id.clone(),
+            token_source: self.token_source.clone(),
+            hf_revision: self.hf_revision.clone(),
+            write_uqff: self.write_uqff.clone(),
+            from_uqff: self.from_uqff.clone(),
+            imatrix: self.imatrix.clone(),
+            calibration_file: self.calibration_file.clone(),
+            chat_template: self.chat_template.clone(),
+            jinja_explicit: self.jinja_explicit.clone(),
+            tokenizer_json: self.tokenizer_json.clone(),
+            device_mapping: self.device_mapping.clone(),
+            hf_cache_path: self.hf_cache_path.clone(),
+            mcp_client_config: self.mcp_client_config.clone(),
+            search_bert_model: self.search_bert_model.clone(),
+            prompt_chunksize: self.prompt_chunksize,
+            topology: self.topology.clone(),
+            organization: self.organization,
+            loader_type: self.loader_type.clone(),
+            dtype: self.dtype,
+            force_cpu: self.force_cpu,
+            isq: self.isq,
+            throughput_logging: self.throughput_logging,
+            profiler: self.profiler.clone(),
+            paged_attn_cfg: self.paged_attn_cfg.clone(),
+            max_num_seqs: self.max_num_seqs,
+            no_kv_cache: self.no_kv_cache,
+            with_logging: self.with_logging,
+            prefix_cache_n: self.prefix_cache_n,
+        };
+        Ok(serde_json::to_value(manifest)?)
+    }
+
+    /// Reconstruct a builder from a manifest produced by [`Self::to_manifest`]. Knobs
+    /// not present in the manifest (callbacks, tools, the JSON metrics sink, the
+    /// [`Device`] handle) keep their
+    /// defaults and must be re-applied by the caller.
+    pub fn from_manifest(value: serde_json::Value) -> anyhow::Result<Self> {
+        let manifest: TextModelManifest = serde_json::from_value(value)?;
+        let mut builder = Self::new(manifest.model_id);
+        builder.token_source = manifest.token_source;
+        builder.hf_revision = manifest.hf_revision;
+        builder.write_uqff = manifest.write_uqff;
+        builder.from_uqff = manifest.from_uqff;
+        builder.imatrix = manifest.imatrix;
+        builder.calibration_file = manifest.calibration_file;
+        builder.chat_template = manifest.chat_template;
+        builder.jinja_explicit = manifest.jinja_explicit;
+        builder.tokenizer_json = manifest.tokenizer_json;
+        builder.device_mapping = manifest.device_mapping;
+        builder.hf_cache_path = manifest.hf_cache_path;
+        builder.mcp_client_config = manifest.mcp_client_config;
+        builder.search_bert_model = manifest.search_bert_model;
+        builder.prompt_chunksize = manifest.prompt_chunksize;
+        builder.topology = manifest.topology;
+        builder.organization = manifest.organization;
+        builder.loader_type = manifest.loader_type;
+        builder.dtype = manifest.dtype;
+        builder.force_cpu = manifest.force_cpu;
+        builder.isq = manifest.isq;
+        builder.throughput_logging = manifest.throughput_logging;
+        builder.profiler = manifest.profiler;
+        builder.paged_attn_cfg = manifest.paged_attn_cfg;
+        builder.max_num_seqs = manifest.max_num_seqs;
+        builder.no_kv_cache = manifest.no_kv_cache;
+        builder.with_logging = manifest.with_logging;
+        builder.prefix_cache_n = manifest.prefix_cache_n;
+        Ok(builder)
+    }
+
     pub async fn build(self) -> anyhow::Result<Model> {
+        // When writing a UQFF artifact, stash a manifest of the builder settings next
+        // to the other standalone-load files (`residual.safetensors`, `tokenizer.json`,
+        // `config.json`, ...). The sidecar points `from_uqff` at the written artifact
+        // and clears `write_uqff` so reloading it loads the artifact standalone instead
+        // of re-downloading and re-quantizing.
+        let uqff_manifest = match self
+            .write_uqff
+            .as_ref()
+            .and_then(|artifact| artifact.parent().map(|parent| (parent, artifact)))
+        {
+            Some((parent, artifact)) => {
+                let mut standalone = self.clone();
+                standalone.from_uqff = Some(vec![artifact.clone()]);
+                standalone.write_uqff = None;
+                Some((parent.to_path_buf(), standalone.to_manifest()?))
+            }
+            None => None,
+        };
+
         let config = NormalSpecificConfig {
             prompt_chunksize: self.prompt_chunksize,
             topology: self.topology,
@@ -370,17 +494,35 @@ impl TextModelBuilder {
             initialize_logging();
         }
 
-        let loader = NormalLoaderBuilder::new(
-            config,
-            self.chat_template,
-            self.tokenizer_json,
-            Some(self.model_id),
-            self.no_kv_cache,
-            self.jinja_explicit,
-        )
-        .build(self.loader_type)?;
-
-        // Load, into a Pipeline
+        let profiler = self
+            .profiler
+            .as_ref()
+            .map(crate::profiler::Profiler::new)
+            .transpose()?;
+        let _build_event = profiler.as_ref().map(|p| p.event("build"));
+
+        let metrics = self
+            .json_metrics
+            .clone()
+            .map(crate::metrics::MetricsEmitter::new)
+            .transpose()?;
+        let model_id = self.model_id.clone();
+
+        let loader = {
+            let _e = profiler.as_ref().map(|p| p.event("build_loader"));
+            NormalLoaderBuilder::new(
+                config,
+                self.chat_template,
+                self.tokenizer_json,
+                Some(self.model_id),
+                self.no_kv_cache,
+                self.jinja_explicit,
+            )
+            .build(self.loader_type)?
+        };
+
+        // Load, into a Pipeline.
+        let load_event = profiler.as_ref().map(|p| p.event("load_model_from_hf"));
         let pipeline = loader.load_model_from_hf(
             self.hf_revision,
             self.token_source,
@@ -392,7 +534,24 @@ impl TextModelBuilder {
             self.isq,
             self.paged_attn_cfg,
         )?;
+        drop(load_event);
+
+        if let Some((parent, manifest)) = uqff_manifest {
+            std::fs::write(
+                parent.join("manifest.json"),
+                serde_json::to_string_pretty(&manifest)?,
+            )?;
+        }
+
+        if let Some(metrics) = &metrics {
+            metrics.emit(crate::metrics::MetricsEvent::ModelLoaded {
+                model_id,
+                dtype: format!("{:?}", self.dtype),
+                isq: self.isq.map(|isq| format!("{isq:?}")),
+            });
+        }
 
+        let scheduler_event = profiler.as_ref().map(|p| p.event("build_scheduler"));
         let scheduler_method = match self.paged_attn_cfg {
             Some(_) => {
                 let config = pipeline
@@ -418,6 +577,7 @@ impl TextModelBuilder {
                 method: DefaultSchedulerMethod::Fixed(self.max_num_seqs.try_into()?),
             },
         };
+        drop(scheduler_event);
 
         let mut runner = MistralRsBuilder::new(
             pipeline,
@@ -449,10 +609,68 @@ impl TextModelBuilder {
             runner = runner.with_prefix_cache_n(n)
         }
 
-        Ok(Model::new(runner.build().await))
+        let build_runner_event = profiler.as_ref().map(|p| p.event("build_runner"));
+        let runner = runner.build().await;
+        drop(build_runner_event);
+
+        Ok(Model::new(runner))
     }
 }
 
+/// Plain-data projection of a [`TextModelBuilder`] used to round-trip its
+/// configuration through JSON. Callbacks, registered tools, the JSON metrics sink,
+/// and the [`Device`] handle are omitted.
+#[derive(Serialize, Deserialize)]
+struct TextModelManifest {
+    model_id: String,
+    token_source: TokenSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hf_revision: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    write_uqff: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    from_uqff: Option<Vec<PathBuf>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    imatrix: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    calibration_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chat_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jinja_explicit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tokenizer_json: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    device_mapping: Option<DeviceMapSetting>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hf_cache_path: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mcp_client_config: Option<McpClientConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    search_bert_model: Option<BertEmbeddingModel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prompt_chunksize: Option<NonZeroUsize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    topology: Option<Topology>,
+    organization: IsqOrganization,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    loader_type: Option<NormalLoaderType>,
+    dtype: ModelDType,
+    force_cpu: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    isq: Option<IsqType>,
+    throughput_logging: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profiler: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    paged_attn_cfg: Option<PagedAttentionConfig>,
+    max_num_seqs: usize,
+    no_kv_cache: bool,
+    with_logging: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prefix_cache_n: Option<usize>,
+}
+
 #[derive(Clone)]
 /// Configure a UQFF text model with the various parameters for loading, running, and other inference behaviors.
 /// This wraps and implements `DerefMut` for the TextModelBuilder, so users should take care to not call UQFF-related methods.